@@ -0,0 +1,22 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compiles every `.capnp` schema under `schema/` into Rust bindings via
+//! `capnpc`, picked up by `ipc::capnp_codec` through `include!(concat!(env!("OUT_DIR"), ...))`.
+
+fn main() {
+    let schema_dir = std::path::Path::new("schema");
+    let mut command = capnpc::CompilerCommand::new();
+    command.src_prefix(schema_dir);
+
+    let entries = std::fs::read_dir(schema_dir).expect("failed to read capnp schema directory");
+    for entry in entries {
+        let path = entry.expect("failed to read schema directory entry").path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some("capnp") {
+            println!("cargo:rerun-if-changed={}", path.display());
+            command.file(&path);
+        }
+    }
+
+    command.run().expect("failed to compile capnp schemas");
+}
@@ -0,0 +1,18 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Feeds arbitrary bytes straight into `TransactionEnvelope::from_xdr`
+//! under `Limits::none()`, the same way `run_simulation` decodes
+//! `envelope_xdr` in the simulator. A crash here is a crash in the
+//! simulator's envelope decode step: it runs inside `simulate`'s
+//! `panic::catch_unwind` guard, so a panic becomes an error response, but
+//! an abort still takes down the whole process, `--serve` included.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use soroban_env_host::xdr::{Limits, ReadXdr, TransactionEnvelope};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = TransactionEnvelope::from_xdr(data, Limits::none());
+});
@@ -0,0 +1,14 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mirrors the `LedgerKey` half of the `ledger_entries` decode loop in
+//! `run_simulation`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use soroban_env_host::xdr::{LedgerKey, Limits, ReadXdr};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = LedgerKey::from_xdr(data, Limits::none());
+});
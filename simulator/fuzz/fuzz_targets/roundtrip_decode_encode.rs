@@ -0,0 +1,29 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Differential target: for any input that decodes cleanly as a
+//! `LedgerKey`, re-encoding
+//! and decoding it again must reproduce the exact same value. A mismatch
+//! here means `from_xdr(to_xdr(x), Limits::none()) != x`, almost always a
+//! `Limits`-related asymmetry between the read and write paths rather
+//! than a crash.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use soroban_env_host::xdr::{LedgerKey, Limits, ReadXdr, WriteXdr};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(decoded) = LedgerKey::from_xdr(data, Limits::none()) else {
+        return;
+    };
+
+    let Ok(reencoded) = decoded.to_xdr(Limits::none()) else {
+        return;
+    };
+
+    let redecoded = LedgerKey::from_xdr(&reencoded, Limits::none())
+        .expect("re-encoding a successfully decoded LedgerKey must itself decode");
+
+    assert_eq!(decoded, redecoded, "from_xdr(to_xdr(x)) != x for a LedgerKey");
+});
@@ -0,0 +1,27 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Differential target: the `LedgerEntry` counterpart of
+//! `roundtrip_decode_encode`, which covers `LedgerKey`. Ledger entries carry nested, variable-length
+//! contract data and code payloads, making them a second likely spot for
+//! a `Limits`-related asymmetry between the read and write paths.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use soroban_env_host::xdr::{LedgerEntry, Limits, ReadXdr, WriteXdr};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(decoded) = LedgerEntry::from_xdr(data, Limits::none()) else {
+        return;
+    };
+
+    let Ok(reencoded) = decoded.to_xdr(Limits::none()) else {
+        return;
+    };
+
+    let redecoded = LedgerEntry::from_xdr(&reencoded, Limits::none())
+        .expect("re-encoding a successfully decoded LedgerEntry must itself decode");
+
+    assert_eq!(decoded, redecoded, "from_xdr(to_xdr(x)) != x for a LedgerEntry");
+});
@@ -0,0 +1,31 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Differential target: the `TransactionEnvelope` counterpart of
+//! `roundtrip_decode_encode`, which covers `LedgerKey`. Envelopes carry nested, variable-length
+//! operation and signature lists, making them the likeliest of the three
+//! decoded types to trip a `Limits`-related asymmetry between the read
+//! and write paths.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use soroban_env_host::xdr::{Limits, ReadXdr, TransactionEnvelope, WriteXdr};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(decoded) = TransactionEnvelope::from_xdr(data, Limits::none()) else {
+        return;
+    };
+
+    let Ok(reencoded) = decoded.to_xdr(Limits::none()) else {
+        return;
+    };
+
+    let redecoded = TransactionEnvelope::from_xdr(&reencoded, Limits::none())
+        .expect("re-encoding a successfully decoded TransactionEnvelope must itself decode");
+
+    assert_eq!(
+        decoded, redecoded,
+        "from_xdr(to_xdr(x)) != x for a TransactionEnvelope"
+    );
+});
@@ -0,0 +1,198 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Content-addressed on-disk cache for simulation results.
+//!
+//! Repeated what-if runs and regression suites tend to replay the exact
+//! same `SimulationRequest` (same envelope, same mainnet snapshot, same
+//! ledger state) over and over. Keying the cache on a SHA-256 of the
+//! canonicalized request lets those replays skip straight to a stored
+//! `SimulationResponse` instead of re-running the host.
+
+use std::fs;
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+
+use crate::{SimulationRequest, SimulationResponse};
+
+/// Bumped whenever the shape of a cached response, or the host/crate
+/// version that produced it, changes — folded into the key so entries
+/// written by an older build are never served back.
+const CACHE_VERSION: &str = "1";
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: Option<PathBuf>,
+}
+
+impl Cache {
+    /// `dir` is the `--cache-dir` value, if any; `disabled` is `--no-cache`.
+    /// An explicit `--no-cache` always wins over `--cache-dir`.
+    pub fn new(dir: Option<&str>, disabled: bool) -> Self {
+        Cache {
+            dir: if disabled { None } else { dir.map(PathBuf::from) },
+        }
+    }
+
+    /// Returns the cache key for `request`, or `None` if caching is
+    /// disabled — callers can use this to skip hashing entirely.
+    pub fn key_for(&self, request: &SimulationRequest) -> Option<String> {
+        self.dir.as_ref().map(|_| cache_key(request))
+    }
+
+    pub fn get(&self, key: &str) -> Option<SimulationResponse> {
+        let bytes = fs::read(self.entry_path(key)?).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    pub fn put(&self, key: &str, response: &SimulationResponse) {
+        let Some(path) = self.entry_path(key) else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            if fs::create_dir_all(parent).is_err() {
+                return;
+            }
+        }
+        if let Ok(bytes) = serde_json::to_vec(response) {
+            let _ = fs::write(path, bytes);
+        }
+    }
+
+    fn entry_path(&self, key: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(format!("{}.json", key)))
+    }
+}
+
+/// Canonicalizes the cache-relevant fields of `request` and hashes them
+/// with SHA-256. Ledger entries are sorted by key first so `HashMap`
+/// iteration order never changes the hash. Every field that can change
+/// `simulate`'s output must be folded in here — `wasm_path`/`mock_args`
+/// select an entirely different (local replay) code path, and
+/// `profile`/`enable_optimization_advisor` each add fields to the
+/// response, so two requests that only differ in one of those must not
+/// collide on the same key.
+fn cache_key(request: &SimulationRequest) -> String {
+    let mut entries: Vec<(&String, &String)> = request
+        .ledger_entries
+        .iter()
+        .flat_map(|map| map.iter())
+        .collect();
+    entries.sort_by_key(|(key, _)| key.as_str());
+
+    let mut hasher = Sha256::new();
+    hasher.update(CACHE_VERSION.as_bytes());
+    hasher.update(request.network.as_deref().unwrap_or("").as_bytes());
+    hasher.update(request.envelope_xdr.as_bytes());
+    for (key, entry) in entries {
+        hasher.update(key.as_bytes());
+        hasher.update(entry.as_bytes());
+    }
+    hasher.update(request.timestamp.unwrap_or_default().to_le_bytes());
+    hasher.update(request.ledger_sequence.unwrap_or_default().to_le_bytes());
+    hasher.update(request.wasm_path.as_deref().unwrap_or("").as_bytes());
+    hasher.update([0u8]);
+    for arg in request.mock_args.iter().flatten() {
+        hasher.update(arg.as_bytes());
+        hasher.update([0u8]);
+    }
+    hasher.update([request.profile.unwrap_or(false) as u8]);
+    hasher.update([request.enable_optimization_advisor as u8]);
+
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_request() -> SimulationRequest {
+        SimulationRequest {
+            network: None,
+            envelope_xdr: "AAAA".to_string(),
+            result_meta_xdr: "BBBB".to_string(),
+            ledger_entries: None,
+            timestamp: None,
+            ledger_sequence: None,
+            wasm_path: None,
+            mock_args: None,
+            profile: None,
+            enable_optimization_advisor: false,
+        }
+    }
+
+    #[test]
+    fn differing_wasm_path_changes_the_key() {
+        let mut with_wasm = base_request();
+        with_wasm.wasm_path = Some("a.wasm".to_string());
+        let mut with_other_wasm = base_request();
+        with_other_wasm.wasm_path = Some("b.wasm".to_string());
+
+        assert_ne!(cache_key(&base_request()), cache_key(&with_wasm));
+        assert_ne!(cache_key(&with_wasm), cache_key(&with_other_wasm));
+    }
+
+    #[test]
+    fn differing_mock_args_changes_the_key() {
+        let mut first = base_request();
+        first.mock_args = Some(vec!["invoke".to_string(), "a".to_string()]);
+        let mut second = base_request();
+        second.mock_args = Some(vec!["invoke".to_string(), "b".to_string()]);
+
+        assert_ne!(cache_key(&first), cache_key(&second));
+    }
+
+    #[test]
+    fn differing_profile_flag_changes_the_key() {
+        let mut profiled = base_request();
+        profiled.profile = Some(true);
+
+        assert_ne!(cache_key(&base_request()), cache_key(&profiled));
+    }
+
+    #[test]
+    fn differing_optimization_advisor_flag_changes_the_key() {
+        let mut advised = base_request();
+        advised.enable_optimization_advisor = true;
+
+        assert_ne!(cache_key(&base_request()), cache_key(&advised));
+    }
+
+    #[test]
+    fn cache_round_trips_through_put_and_get() {
+        let dir = std::env::temp_dir().join(format!(
+            "erst-simulator-cache-test-{}-{}",
+            std::process::id(),
+            "round-trip"
+        ));
+        let _ = fs::remove_dir_all(&dir);
+
+        let cache = Cache::new(Some(dir.to_str().unwrap()), false);
+        let key = cache.key_for(&base_request()).unwrap();
+        assert!(cache.get(&key).is_none());
+
+        let response = SimulationResponse {
+            status: "success".to_string(),
+            error: None,
+            events: vec![],
+            logs: vec!["hit".to_string()],
+            flamegraph: None,
+            profile: None,
+            optimization_report: None,
+            budget_usage: None,
+        };
+        cache.put(&key, &response);
+
+        let cached = cache.get(&key).expect("entry was just written");
+        assert_eq!(cached.logs, response.logs);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn disabled_cache_never_produces_a_key() {
+        let cache = Cache::new(Some("/tmp/unused"), true);
+        assert_eq!(cache.key_for(&base_request()), None);
+    }
+}
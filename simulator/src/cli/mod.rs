@@ -0,0 +1,150 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod trace_viewer;
+
+use std::env;
+
+/// Wire protocol spoken by `--serve`'s listeners.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Protocol {
+    /// JSON-RPC 2.0, either as an HTTP body or length-prefixed on the Unix
+    /// socket.
+    #[default]
+    Json,
+    /// Cap'n Proto messages, self-framed by their own segment table, on
+    /// whichever listener(s) are configured.
+    Capnp,
+}
+
+/// Transport configuration for `--serve` mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServeArgs {
+    /// Address to bind the listener to.
+    pub http_bind: String,
+    /// Optional Unix domain socket path, in addition to the bind address.
+    pub unix_socket: Option<String>,
+    /// `--protocol=json|capnp`, selecting the wire format for both
+    /// listeners. Defaults to JSON.
+    pub protocol: Protocol,
+}
+
+/// Parsed command-line invocation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Args {
+    /// Present when the process should run as a long-lived server (JSON-RPC
+    /// or Cap'n Proto, per `ServeArgs::protocol`) instead of reading a
+    /// single request from stdin.
+    pub serve: Option<ServeArgs>,
+    /// `--cache-dir=PATH`: directory backing the content-addressed
+    /// simulation cache. No caching happens unless this is set.
+    pub cache_dir: Option<String>,
+    /// `--no-cache`: disables the cache even if `--cache-dir` is set.
+    pub no_cache: bool,
+}
+
+impl Args {
+    /// Parses `std::env::args()`. Defaults to the stdin one-shot path when
+    /// `--serve` is not present, preserving the existing invocation.
+    pub fn parse() -> Self {
+        Self::parse_from(env::args().skip(1))
+    }
+
+    fn parse_from(args: impl Iterator<Item = String>) -> Self {
+        let mut serve = false;
+        let mut http_bind = None;
+        let mut unix_socket = None;
+        let mut protocol = Protocol::default();
+        let mut cache_dir = None;
+        let mut no_cache = false;
+
+        for arg in args {
+            if arg == "--serve" {
+                serve = true;
+            } else if arg == "--no-cache" {
+                no_cache = true;
+            } else if let Some(value) = arg.strip_prefix("--bind=") {
+                http_bind = Some(value.to_string());
+            } else if let Some(value) = arg.strip_prefix("--socket=") {
+                unix_socket = Some(value.to_string());
+            } else if let Some(value) = arg.strip_prefix("--cache-dir=") {
+                cache_dir = Some(value.to_string());
+            } else if let Some(value) = arg.strip_prefix("--protocol=") {
+                protocol = match value {
+                    "capnp" => Protocol::Capnp,
+                    _ => Protocol::Json,
+                };
+            }
+        }
+
+        Self {
+            serve: serve.then(|| ServeArgs {
+                http_bind: http_bind.unwrap_or_else(|| "127.0.0.1:8787".to_string()),
+                unix_socket,
+                protocol,
+            }),
+            cache_dir,
+            no_cache,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(raw: &[&str]) -> Args {
+        Args::parse_from(raw.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn defaults_to_stdin_mode() {
+        assert_eq!(args(&[]).serve, None);
+    }
+
+    #[test]
+    fn serve_uses_default_bind_address() {
+        let parsed = args(&["--serve"]);
+        assert_eq!(
+            parsed.serve,
+            Some(ServeArgs {
+                http_bind: "127.0.0.1:8787".to_string(),
+                unix_socket: None,
+                protocol: Protocol::Json,
+            })
+        );
+    }
+
+    #[test]
+    fn serve_accepts_bind_and_socket_overrides() {
+        let parsed = args(&["--serve", "--bind=0.0.0.0:9000", "--socket=/tmp/erst.sock"]);
+        assert_eq!(
+            parsed.serve,
+            Some(ServeArgs {
+                http_bind: "0.0.0.0:9000".to_string(),
+                unix_socket: Some("/tmp/erst.sock".to_string()),
+                protocol: Protocol::Json,
+            })
+        );
+    }
+
+    #[test]
+    fn serve_accepts_capnp_protocol() {
+        let parsed = args(&["--serve", "--protocol=capnp"]);
+        assert_eq!(parsed.serve.unwrap().protocol, Protocol::Capnp);
+    }
+
+    #[test]
+    fn cache_dir_defaults_to_disabled() {
+        let parsed = args(&[]);
+        assert_eq!(parsed.cache_dir, None);
+        assert!(!parsed.no_cache);
+    }
+
+    #[test]
+    fn parses_cache_dir_and_no_cache() {
+        let parsed = args(&["--cache-dir=/tmp/erst-cache", "--no-cache"]);
+        assert_eq!(parsed.cache_dir, Some("/tmp/erst-cache".to_string()));
+        assert!(parsed.no_cache);
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Cap'n Proto framing for `--protocol=capnp`.
+//!
+//! JSON-over-stdin (and the JSON-RPC transports in the parent `ipc`
+//! module) are brittle for embedding the simulator in other-language
+//! hosts: there's no schema and no generated client bindings. This codec
+//! reads and writes `simulation.capnp`-defined messages directly on the
+//! wire, self-framed by Cap'n Proto's own segment table, with no extra
+//! length prefix needed. Decoding is not zero-copy: `read_request` copies
+//! every field, each `ledger_entries` pair included, into an owned
+//! `SimulationRequest`.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use capnp::message::{Builder, ReaderOptions};
+use capnp::serialize;
+
+use crate::{SimulationRequest, SimulationResponse};
+
+#[allow(dead_code, unused_qualifications, clippy::all)]
+pub mod simulation_capnp {
+    include!(concat!(env!("OUT_DIR"), "/simulation_capnp.rs"));
+}
+
+use simulation_capnp::{simulation_request, simulation_response};
+
+/// Reads one `SimulationRequest` message off `stream`.
+pub fn read_request(stream: &mut impl Read) -> capnp::Result<SimulationRequest> {
+    let message = serialize::read_message(stream, ReaderOptions::new())?;
+    let reader = message.get_root::<simulation_request::Reader>()?;
+
+    let ledger_entries = if reader.has_ledger_entries() {
+        let mut map = HashMap::new();
+        for pair in reader.get_ledger_entries()?.iter() {
+            map.insert(
+                pair.get_key_xdr()?.to_string()?,
+                pair.get_entry_xdr()?.to_string()?,
+            );
+        }
+        Some(map)
+    } else {
+        None
+    };
+
+    let mock_args = if reader.has_mock_args() {
+        let mut args = Vec::new();
+        for arg in reader.get_mock_args()?.iter() {
+            args.push(arg.to_string()?);
+        }
+        Some(args)
+    } else {
+        None
+    };
+
+    Ok(SimulationRequest {
+        network: reader
+            .has_network()
+            .then(|| reader.get_network())
+            .transpose()?
+            .map(|t| t.to_string())
+            .transpose()?,
+        envelope_xdr: reader.get_envelope_xdr()?.to_string()?,
+        result_meta_xdr: reader.get_result_meta_xdr()?.to_string()?,
+        ledger_entries,
+        timestamp: reader.get_has_timestamp().then(|| reader.get_timestamp()),
+        ledger_sequence: reader
+            .get_has_ledger_sequence()
+            .then(|| reader.get_ledger_sequence()),
+        wasm_path: reader
+            .has_wasm_path()
+            .then(|| reader.get_wasm_path())
+            .transpose()?
+            .map(|t| t.to_string())
+            .transpose()?,
+        mock_args,
+        profile: reader.get_has_profile().then(|| reader.get_profile()),
+        enable_optimization_advisor: reader.get_enable_optimization_advisor(),
+    })
+}
+
+/// Serializes `response` as one `SimulationResponse` message and writes it
+/// to `stream`.
+pub fn write_response(stream: &mut impl Write, response: &SimulationResponse) -> capnp::Result<()> {
+    let mut message = Builder::new_default();
+    {
+        let mut builder = message.init_root::<simulation_response::Builder>();
+        builder.set_status(&response.status);
+        if let Some(error) = &response.error {
+            builder.set_error(error);
+        }
+        if let Some(flamegraph) = &response.flamegraph {
+            builder.set_flamegraph(flamegraph);
+        }
+        if let Some(profile) = &response.profile {
+            builder.set_profile(profile);
+        }
+
+        let mut events = builder.reborrow().init_events(response.events.len() as u32);
+        for (i, event) in response.events.iter().enumerate() {
+            events.set(i as u32, event);
+        }
+
+        let mut logs = builder.reborrow().init_logs(response.logs.len() as u32);
+        for (i, log) in response.logs.iter().enumerate() {
+            logs.set(i as u32, log);
+        }
+
+        if let Some(budget_usage) = &response.budget_usage {
+            let mut usage = builder.reborrow().init_budget_usage();
+            usage.set_cpu_instructions(budget_usage.cpu_instructions);
+            usage.set_memory_bytes(budget_usage.memory_bytes);
+            usage.set_operations_count(budget_usage.operations_count as u64);
+        }
+
+        if let Some(report) = &response.optimization_report {
+            let mut optimization_report = builder.reborrow().init_optimization_report();
+            optimization_report.set_summary(&report.summary);
+            let mut suggestions = optimization_report.init_suggestions(report.suggestions.len() as u32);
+            for (i, suggestion) in report.suggestions.iter().enumerate() {
+                suggestions.set(i as u32, suggestion);
+            }
+        }
+    }
+
+    serialize::write_message(stream, &message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas_optimizer::OptimizationReport;
+
+    #[test]
+    fn read_request_round_trips_required_and_optional_fields() {
+        let mut message = Builder::new_default();
+        {
+            let mut builder = message.init_root::<simulation_request::Builder>();
+            builder.set_envelope_xdr("AAAA");
+            builder.set_result_meta_xdr("BBBB");
+            builder.set_network("testnet");
+            builder.set_timestamp(42);
+            builder.set_has_timestamp(true);
+            builder.set_wasm_path("contract.wasm");
+        }
+
+        let mut bytes = Vec::new();
+        serialize::write_message(&mut bytes, &message).unwrap();
+
+        let request = read_request(&mut bytes.as_slice()).unwrap();
+        assert_eq!(request.envelope_xdr, "AAAA");
+        assert_eq!(request.result_meta_xdr, "BBBB");
+        assert_eq!(request.network.as_deref(), Some("testnet"));
+        assert_eq!(request.timestamp, Some(42));
+        assert_eq!(request.wasm_path.as_deref(), Some("contract.wasm"));
+        assert_eq!(request.ledger_sequence, None);
+    }
+
+    #[test]
+    fn write_response_round_trips_the_optimization_report() {
+        let response = SimulationResponse {
+            status: "success".to_string(),
+            error: None,
+            events: vec!["event-a".to_string()],
+            logs: vec!["log-a".to_string()],
+            flamegraph: None,
+            profile: None,
+            optimization_report: Some(OptimizationReport {
+                summary: "looks fine".to_string(),
+                suggestions: vec!["batch reads".to_string()],
+            }),
+            budget_usage: None,
+        };
+
+        let mut bytes = Vec::new();
+        write_response(&mut bytes, &response).unwrap();
+
+        let message = serialize::read_message(&mut bytes.as_slice(), ReaderOptions::new()).unwrap();
+        let reader = message.get_root::<simulation_response::Reader>().unwrap();
+
+        assert_eq!(reader.get_status().unwrap().to_string().unwrap(), "success");
+        let report = reader.get_optimization_report().unwrap();
+        assert_eq!(report.get_summary().unwrap().to_string().unwrap(), "looks fine");
+        assert_eq!(report.get_suggestions().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,400 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! JSON-RPC 2.0 and Cap'n Proto transports for `--serve` mode.
+//!
+//! A one-shot process per simulation throws away the warmed-up
+//! `soroban_env_host` setup on every invocation. This module keeps a single
+//! process alive and answers `simulateTransaction` calls over HTTP and,
+//! optionally, a Unix domain socket, including batched request arrays per
+//! the JSON-RPC 2.0 spec.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::thread;
+
+use serde_json::Value;
+
+mod capnp_codec;
+
+use crate::cache::Cache;
+use crate::cli::{Protocol, ServeArgs};
+use crate::{SimulationRequest, SimulationResponse};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Largest request body either listener will allocate for, in bytes. A
+/// client-supplied length past this is rejected with a JSON-RPC parse
+/// error instead of being handed straight to `vec![0u8; len]` — an
+/// unbounded allocation there is an easy way to abort the whole
+/// long-running `--serve` process, and that abort happens below any
+/// `panic::catch_unwind` guard.
+const MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+/// Runs the server described by `args` (JSON-RPC or Cap'n Proto, per
+/// `args.protocol`) until the process is killed. Blocks the calling thread,
+/// which runs the HTTP listener; the Unix socket listener, if configured,
+/// runs on its own thread. Each accepted connection gets a thread. `cache`
+/// backs every `simulateTransaction` call handled by either listener.
+pub fn serve(args: &ServeArgs, cache: Cache) -> io::Result<()> {
+    let unix_handle = match args.unix_socket.clone() {
+        Some(path) => {
+            let unix_cache = cache.clone();
+            let protocol = args.protocol;
+            let handle = thread::spawn(move || {
+                if let Err(e) = run_unix_server(&path, unix_cache, protocol) {
+                    eprintln!("unix socket server error: {}", e);
+                }
+            });
+            Some(handle)
+        }
+        None => None,
+    };
+
+    eprintln!(
+        "erst-simulator: listening for {:?} on {}",
+        args.protocol, args.http_bind
+    );
+    run_http_server(&args.http_bind, cache, args.protocol)?;
+
+    if let Some(handle) = unix_handle {
+        let _ = handle.join();
+    }
+    Ok(())
+}
+
+fn run_http_server(bind_addr: &str, cache: Cache, protocol: Protocol) -> io::Result<()> {
+    let listener = TcpListener::bind(bind_addr)?;
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let cache = cache.clone();
+                thread::spawn(move || {
+                    let mut stream = stream;
+                    let result = match protocol {
+                        Protocol::Json => handle_json_http_connection(&mut stream, &cache),
+                        Protocol::Capnp => handle_capnp_stream(&mut stream, &cache),
+                    };
+                    if let Err(e) = result {
+                        eprintln!("http connection error: {}", e);
+                    }
+                });
+            }
+            Err(e) => eprintln!("failed to accept http connection: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn handle_json_http_connection(stream: &mut TcpStream, cache: &Cache) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    if read_bounded_line(&mut reader, &mut request_line)?.is_none() {
+        return reject_oversized_http_request(stream, "request line exceeds the maximum allowed size");
+    }
+
+    let mut content_length: usize = 0;
+    loop {
+        let mut header = String::new();
+        let Some(bytes_read) = read_bounded_line(&mut reader, &mut header)? else {
+            return reject_oversized_http_request(stream, "header line exceeds the maximum allowed size");
+        };
+        if bytes_read == 0 {
+            break;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if header.to_lowercase().starts_with("content-length:") {
+            content_length = header
+                .splitn(2, ':')
+                .nth(1)
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(0);
+        }
+    }
+
+    if content_length > MAX_BODY_BYTES {
+        return reject_oversized_http_request(
+            stream,
+            &format!(
+                "Content-Length {} exceeds the maximum allowed body size of {} bytes",
+                content_length, MAX_BODY_BYTES
+            ),
+        );
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+
+    let response_body = dispatch(&body, cache);
+    let http_response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response_body.len()
+    );
+    stream.write_all(http_response.as_bytes())?;
+    stream.write_all(&response_body)?;
+    stream.flush()
+}
+
+/// Reads one `\n`-terminated line into `buf` a byte at a time, bailing out
+/// with `None` as soon as the line would grow past `MAX_BODY_BYTES`
+/// instead of letting a client that never sends a newline (or never sends
+/// `Content-Length`) grow it without bound via `BufRead::read_line` — the
+/// same allocation-abort risk the body-size cap guards against, just on
+/// the header side of the parse. Returns `Some(0)` on a clean EOF,
+/// matching `BufRead::read_line`.
+fn read_bounded_line(reader: &mut impl BufRead, buf: &mut String) -> io::Result<Option<usize>> {
+    let mut raw = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        if reader.read(&mut byte)? == 0 {
+            break;
+        }
+        raw.push(byte[0]);
+        if byte[0] == b'\n' {
+            break;
+        }
+        if raw.len() > MAX_BODY_BYTES {
+            return Ok(None);
+        }
+    }
+    buf.push_str(&String::from_utf8_lossy(&raw));
+    Ok(Some(raw.len()))
+}
+
+/// Writes a `413 Payload Too Large` response carrying a JSON-RPC parse
+/// error, for either the request/header line or `Content-Length` cap.
+fn reject_oversized_http_request(stream: &mut TcpStream, message: &str) -> io::Result<()> {
+    let response_body =
+        serde_json::to_vec(&error_response(Value::Null, PARSE_ERROR, message.to_string())).unwrap_or_default();
+    let http_response = format!(
+        "HTTP/1.1 413 Payload Too Large\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response_body.len()
+    );
+    stream.write_all(http_response.as_bytes())?;
+    stream.write_all(&response_body)?;
+    stream.flush()
+}
+
+fn run_unix_server(path: &str, cache: Cache, protocol: Protocol) -> io::Result<()> {
+    let _ = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path)?;
+    for stream in listener.incoming() {
+        match stream {
+            Ok(mut stream) => {
+                let cache = cache.clone();
+                thread::spawn(move || {
+                    let result = match protocol {
+                        Protocol::Json => handle_json_unix_connection(&mut stream, &cache),
+                        Protocol::Capnp => handle_capnp_stream(&mut stream, &cache),
+                    };
+                    if let Err(e) = result {
+                        eprintln!("unix connection error: {}", e);
+                    }
+                });
+            }
+            Err(e) => eprintln!("failed to accept unix connection: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// The Unix socket transport trades HTTP framing for simple
+/// length-prefixed messages: a big-endian `u32` byte count followed by the
+/// JSON-RPC payload, repeated for as long as the connection stays open.
+fn handle_json_unix_connection(stream: &mut UnixStream, cache: &Cache) -> io::Result<()> {
+    loop {
+        let mut len_bytes = [0u8; 4];
+        if let Err(e) = stream.read_exact(&mut len_bytes) {
+            if e.kind() == io::ErrorKind::UnexpectedEof {
+                return Ok(());
+            }
+            return Err(e);
+        }
+        let len = u32::from_be_bytes(len_bytes) as usize;
+        if len > MAX_BODY_BYTES {
+            let response_body = serde_json::to_vec(&error_response(
+                Value::Null,
+                PARSE_ERROR,
+                format!(
+                    "Message length {} exceeds the maximum allowed body size of {} bytes",
+                    len, MAX_BODY_BYTES
+                ),
+            ))
+            .unwrap_or_default();
+            stream.write_all(&(response_body.len() as u32).to_be_bytes())?;
+            stream.write_all(&response_body)?;
+            return Ok(());
+        }
+
+        let mut body = vec![0u8; len];
+        stream.read_exact(&mut body)?;
+
+        let response_body = dispatch(&body, cache);
+        stream.write_all(&(response_body.len() as u32).to_be_bytes())?;
+        stream.write_all(&response_body)?;
+    }
+}
+
+/// The Cap'n Proto transport needs no length prefix of its own: every
+/// message is already self-framed by its segment table, so this just loops
+/// reading one `simulation.capnp` request and writing back one response
+/// until the peer hangs up, on either listener.
+fn handle_capnp_stream<S: Read + Write>(stream: &mut S, cache: &Cache) -> io::Result<()> {
+    loop {
+        let request = match capnp_codec::read_request(stream) {
+            Ok(r) => r,
+            Err(e) if e.kind == capnp::ErrorKind::Disconnected => return Ok(()),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+
+        let response = crate::simulate_cached(request, cache);
+        capnp_codec::write_response(stream, &response)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    }
+}
+
+/// Parses `body` as either a single JSON-RPC request object or a batch
+/// array, runs each through `simulateTransaction`, and serializes the
+/// matching single object or array response.
+fn dispatch(body: &[u8], cache: &Cache) -> Vec<u8> {
+    let parsed: Result<Value, _> = serde_json::from_slice(body);
+    let value = match parsed {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::to_vec(&error_response(Value::Null, PARSE_ERROR, format!("Parse error: {}", e)))
+                .unwrap_or_default();
+        }
+    };
+
+    if let Value::Array(requests) = value {
+        let responses: Vec<Value> = requests.into_iter().map(|r| handle_single(r, cache)).collect();
+        serde_json::to_vec(&responses).unwrap_or_default()
+    } else {
+        serde_json::to_vec(&handle_single(value, cache)).unwrap_or_default()
+    }
+}
+
+fn handle_single(value: Value, cache: &Cache) -> Value {
+    let id = value.get("id").cloned().unwrap_or(Value::Null);
+
+    let method = match value.get("method").and_then(Value::as_str) {
+        Some(m) => m,
+        None => return error_response(id, INVALID_REQUEST, "Missing \"method\"".to_string()),
+    };
+
+    if method != "simulateTransaction" {
+        return error_response(id, METHOD_NOT_FOUND, format!("Unknown method: {}", method));
+    }
+
+    let params = value.get("params").cloned().unwrap_or(Value::Null);
+    let request: SimulationRequest = match serde_json::from_value(params) {
+        Ok(r) => r,
+        Err(e) => return error_response(id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+
+    let response: SimulationResponse = crate::simulate_cached(request, cache);
+    success_response(id, &response)
+}
+
+fn success_response(id: Value, result: &SimulationResponse) -> Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "result": result,
+        "id": id,
+    })
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_bounded_line_reads_a_normal_line() {
+        let mut reader = BufReader::new("GET / HTTP/1.1\r\n".as_bytes());
+        let mut line = String::new();
+        let read = read_bounded_line(&mut reader, &mut line).unwrap();
+        assert_eq!(read, Some(16));
+        assert_eq!(line, "GET / HTTP/1.1\r\n");
+    }
+
+    #[test]
+    fn read_bounded_line_rejects_a_line_past_the_body_size_cap() {
+        let oversized = vec![b'a'; MAX_BODY_BYTES + 1];
+        let mut reader = BufReader::new(oversized.as_slice());
+        let mut line = String::new();
+        assert_eq!(read_bounded_line(&mut reader, &mut line).unwrap(), None);
+    }
+
+    fn no_cache() -> Cache {
+        Cache::new(None, false)
+    }
+
+    #[test]
+    fn dispatch_reports_parse_error_for_invalid_json() {
+        let cache = no_cache();
+        let response: Value = serde_json::from_slice(&dispatch(b"not json", &cache)).unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+    }
+
+    #[test]
+    fn dispatch_handles_a_batch_of_requests() {
+        let cache = no_cache();
+        let batch = serde_json::json!([
+            {"jsonrpc": "2.0", "method": "unknownMethod", "id": 1},
+            {"jsonrpc": "2.0", "method": "unknownMethod", "id": 2},
+        ]);
+        let response: Value =
+            serde_json::from_slice(&dispatch(&serde_json::to_vec(&batch).unwrap(), &cache)).unwrap();
+        let responses = response.as_array().expect("batch input yields a batch response");
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[1]["id"], 2);
+    }
+
+    #[test]
+    fn handle_single_rejects_a_request_with_no_method() {
+        let response = handle_single(serde_json::json!({"jsonrpc": "2.0", "id": 1}), &no_cache());
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[test]
+    fn handle_single_rejects_an_unknown_method() {
+        let response = handle_single(
+            serde_json::json!({"jsonrpc": "2.0", "method": "doSomethingElse", "id": 1}),
+            &no_cache(),
+        );
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn handle_single_runs_simulate_transaction_and_wraps_the_result() {
+        let response = handle_single(
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "simulateTransaction",
+                "params": {"envelope_xdr": "not-valid-xdr", "result_meta_xdr": ""},
+                "id": 7,
+            }),
+            &no_cache(),
+        );
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["result"]["status"], "error");
+    }
+}
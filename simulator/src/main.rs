@@ -3,6 +3,7 @@
 
 mod theme;
 mod config;
+mod cache;
 mod cli;
 mod ipc;
 mod gas_optimizer;
@@ -10,7 +11,6 @@ mod gas_optimizer;
 use base64::Engine as _;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use soroban_env_host::xdr::ReadXdr;
 use std::collections::HashMap;
 use std::io::{self, Read};
 use std::panic;
@@ -36,7 +36,7 @@ struct SimulationRequest {
     enable_optimization_advisor: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct SimulationResponse {
     status: String,
     error: Option<String>,
@@ -44,12 +44,14 @@ struct SimulationResponse {
     logs: Vec<String>,
     flamegraph: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     optimization_report: Option<OptimizationReport>,
     #[serde(skip_serializing_if = "Option::is_none")]
     budget_usage: Option<BudgetUsage>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct BudgetUsage {
     cpu_instructions: u64,
     memory_bytes: u64,
@@ -81,7 +83,54 @@ fn network_id_from_passphrase(passphrase: &str) -> [u8; 32] {
     out
 }
 
+/// Pulls the declared Soroban resource footprint out of a transaction
+/// envelope, if it has one. Classic (non-Soroban) transactions and `TxV0`
+/// envelopes carry no footprint, so callers fall back to the default host.
+fn soroban_footprint(
+    envelope: &soroban_env_host::xdr::TransactionEnvelope,
+) -> Option<&soroban_env_host::xdr::LedgerFootprint> {
+    let tx = match envelope {
+        soroban_env_host::xdr::TransactionEnvelope::Tx(tx_v1) => &tx_v1.tx,
+        soroban_env_host::xdr::TransactionEnvelope::TxV0(_) => return None,
+        soroban_env_host::xdr::TransactionEnvelope::TxFeeBump(bump) => match &bump.tx.inner_tx {
+            soroban_env_host::xdr::FeeBumpTransactionInnerTx::Tx(tx_v1) => &tx_v1.tx,
+        },
+    };
+
+    match &tx.ext {
+        soroban_env_host::xdr::TransactionExt::V1(soroban_data) => Some(&soroban_data.resources.footprint),
+        _ => None,
+    }
+}
+
+/// Classifies `key` as read-only or read-write per the transaction's
+/// declared footprint, or `None` if the key isn't part of the footprint at
+/// all (in which case the simulator must not inject it into storage).
+fn footprint_access(
+    footprint: &soroban_env_host::xdr::LedgerFootprint,
+    key: &soroban_env_host::xdr::LedgerKey,
+) -> Option<soroban_env_host::storage::AccessType> {
+    if footprint.read_write.as_slice().contains(key) {
+        Some(soroban_env_host::storage::AccessType::ReadWrite)
+    } else if footprint.read_only.as_slice().contains(key) {
+        Some(soroban_env_host::storage::AccessType::ReadOnly)
+    } else {
+        None
+    }
+}
+
 fn main() {
+    let args = cli::Args::parse();
+    let cache = cache::Cache::new(args.cache_dir.as_deref(), args.no_cache);
+
+    if let Some(serve_args) = &args.serve {
+        if let Err(e) = ipc::serve(serve_args, cache) {
+            eprintln!("Failed to start JSON-RPC server: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Read JSON from Stdin
     let mut buffer = String::new();
     if let Err(e) = io::stdin().read_to_string(&mut buffer) {
@@ -91,6 +140,7 @@ fn main() {
             events: vec![],
             logs: vec![],
             flamegraph: None,
+            profile: None,
             optimization_report: None,
             budget_usage: None,
         };
@@ -108,6 +158,7 @@ fn main() {
                 events: vec![],
                 logs: vec![],
                 flamegraph: None,
+                profile: None,
                 optimization_report: None,
                 budget_usage: None,
             };
@@ -116,29 +167,233 @@ fn main() {
         }
     };
 
+    let response = simulate_cached(request, &cache);
+    println!("{}", serde_json::to_string(&response).unwrap());
+}
+
+/// Looks up `request` in `cache` before falling back to a real `simulate`
+/// call, storing the result for next time. A cache hit is called out in
+/// the returned `logs` so a reproducible rerun is visibly distinguishable
+/// from a fresh execution.
+fn simulate_cached(request: SimulationRequest, cache: &cache::Cache) -> SimulationResponse {
+    let key = cache.key_for(&request);
+
+    if let Some(key) = &key {
+        if let Some(mut cached) = cache.get(key) {
+            cached.logs.push(format!("Cache hit (key {}); returning stored response", key));
+            return cached;
+        }
+    }
+
+    let response = simulate(request);
+
+    if let Some(key) = key {
+        cache.put(&key, &response);
+    }
+
+    response
+}
+
+/// Decodes a base64-encoded XDR blob into `T`, under `Limits::none()` like
+/// the rest of the simulator. Hostile input (truncated length prefixes,
+/// deeply nested unions) has been observed to panic inside `from_xdr`
+/// rather than return `Err`, so every call site is made from within
+/// `run_simulation`, which itself runs under `panic::catch_unwind`.
+fn decode_xdr<T: soroban_env_host::xdr::ReadXdr>(label: &str, base64_xdr: &str) -> Result<T, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(base64_xdr)
+        .map_err(|e| format!("Failed to decode {} Base64: {}", label, e))?;
+    T::from_xdr(&bytes, soroban_env_host::xdr::Limits::none())
+        .map_err(|e| format!("Failed to parse {} XDR: {}", label, e))
+}
+
+/// Everything a successful [`simulate`] call needs to build its
+/// [`SimulationResponse`], assembled by [`run_simulation`] inside the
+/// panic guard so the `Host` and its accumulated budget survive to the
+/// reporting step below.
+struct SimulationSuccess {
+    host: soroban_env_host::Host,
+    exec_trace: ExecutionTrace,
+    loaded_entries_count: usize,
+    storage_logs: Vec<String>,
+    operations_count: usize,
+}
+
+/// Runs one simulation end to end: decodes the envelope and any ledger
+/// entries, executes the operations against a fresh `Host`, and reports
+/// budget usage. Shared by the stdin one-shot path and the `--serve`
+/// JSON-RPC transport so both stay on the exact same code path.
+fn simulate(request: SimulationRequest) -> SimulationResponse {
     // Check if this is a local WASM replay (no network data)
     if let Some(wasm_path) = &request.wasm_path {
         return run_local_wasm_replay(wasm_path, &request.mock_args);
     }
 
-    // Decode Envelope XDR
-    let envelope = match base64::engine::general_purpose::STANDARD.decode(&request.envelope_xdr) {
-        Ok(bytes) => match soroban_env_host::xdr::TransactionEnvelope::from_xdr(
-            &bytes,
-            soroban_env_host::xdr::Limits::none(),
-        ) {
-            Ok(env) => env,
-            Err(e) => {
-                return send_error(format!("Failed to parse Envelope XDR: {}", e));
-            }
-        },
-        Err(e) => {
-            return send_error(format!("Failed to decode Envelope Base64: {}", e));
+    // Decoding the envelope and every ledger entry XDR can itself panic on
+    // hostile input (see decode_xdr's doc comment), so it runs under the
+    // same guard as operation execution rather than before it.
+    let guarded = panic::catch_unwind(panic::AssertUnwindSafe(|| run_simulation(&request)));
+
+    let success = match guarded {
+        Ok(Ok(success)) => success,
+        Ok(Err(msg)) => return error_response(msg),
+        Err(panic_info) => {
+            let panic_msg = if let Some(s) = panic_info.downcast_ref::<&str>() {
+                s.to_string()
+            } else if let Some(s) = panic_info.downcast_ref::<String>() {
+                s.clone()
+            } else {
+                "Unknown panic".to_string()
+            };
+
+            return SimulationResponse {
+                status: "error".to_string(),
+                error: Some(format!("Simulator panicked: {}", panic_msg)),
+                events: vec![],
+                logs: vec![format!("PANIC: {}", panic_msg)],
+                flamegraph: None,
+                profile: None,
+                optimization_report: None,
+                budget_usage: None,
+            };
         }
     };
 
-    // Initialize Host
-    let host = soroban_env_host::Host::default();
+    let SimulationSuccess {
+        host,
+        exec_trace,
+        loaded_entries_count,
+        storage_logs,
+        operations_count,
+    } = success;
+
+    // Budget and Reporting
+    let budget = host.budget_cloned();
+    let cpu_insns = budget.get_cpu_insns_consumed().unwrap_or(0);
+    let mem_bytes = budget.get_mem_bytes_consumed().unwrap_or(0);
+
+    let budget_usage = BudgetUsage {
+        cpu_instructions: cpu_insns,
+        memory_bytes: mem_bytes,
+        operations_count,
+    };
+
+    let optimization_report = if request.enable_optimization_advisor {
+        let advisor = GasOptimizationAdvisor::new();
+        let metrics = BudgetMetrics {
+            cpu_instructions: budget_usage.cpu_instructions,
+            memory_bytes: budget_usage.memory_bytes,
+            total_operations: budget_usage.operations_count,
+        };
+        Some(advisor.analyze(&metrics))
+    } else {
+        None
+    };
+
+    let events = match host.get_events() {
+        Ok(evs) => evs.0.iter().map(|e| format!("{:?}", e)).collect(),
+        Err(_) => vec!["Failed to retrieve events".to_string()],
+    };
+
+    let mut final_logs = vec![
+        format!("Host Initialized with Budget: {:?}", budget),
+        format!("Loaded {} Ledger Entries", loaded_entries_count),
+    ];
+    final_logs.extend(storage_logs);
+    final_logs.extend(exec_trace.logs);
+
+    let (flamegraph_svg, profile_text) = if request.profile.unwrap_or(false) {
+        let cpu_svg = render_flamegraph("Soroban CPU Consumption", &exec_trace.cpu_folded, cpu_insns);
+
+        let mut raw_profile = format!("# cpu (instructions)\n{}", exec_trace.cpu_folded);
+        raw_profile.push_str("# memory (bytes)\n");
+        raw_profile.push_str(&exec_trace.mem_folded);
+
+        (cpu_svg, Some(raw_profile))
+    } else {
+        (None, None)
+    };
+
+    SimulationResponse {
+        status: "success".to_string(),
+        error: None,
+        events,
+        logs: final_logs,
+        flamegraph: flamegraph_svg,
+        profile: profile_text,
+        optimization_report,
+        budget_usage: Some(budget_usage),
+    }
+}
+
+/// The decode-and-execute body of [`simulate`], pulled out so it can run
+/// entirely inside one `panic::catch_unwind`. Returns `Err` for anything
+/// that should surface as a clean JSON error rather than a panic: bad
+/// base64 or malformed XDR. A ledger entry outside the footprint, or a
+/// footprint key with no matching entry, is only reported in the logs.
+fn run_simulation(request: &SimulationRequest) -> Result<SimulationSuccess, String> {
+    let envelope: soroban_env_host::xdr::TransactionEnvelope =
+        decode_xdr("Envelope", &request.envelope_xdr)?;
+
+    // Decode each provided (LedgerKey, LedgerEntry) pair and classify it
+    // against the transaction's declared Soroban footprint so the host sees
+    // real contract/instance/code data instead of running against nothing.
+    let declared_footprint = soroban_footprint(&envelope);
+    let mut footprint_entries = Vec::new();
+    let mut storage_entries = Vec::new();
+    let mut storage_logs = Vec::new();
+    let mut seen_keys = std::collections::HashSet::new();
+    let mut loaded_entries_count = 0;
+
+    if let Some(entries) = &request.ledger_entries {
+        for (key_xdr, entry_xdr) in entries {
+            let key: soroban_env_host::xdr::LedgerKey = decode_xdr("LedgerKey", key_xdr)?;
+            let entry: soroban_env_host::xdr::LedgerEntry = decode_xdr("LedgerEntry", entry_xdr)?;
+
+            match declared_footprint.and_then(|fp| footprint_access(fp, &key)) {
+                Some(access) => {
+                    seen_keys.insert(key.clone());
+                    let key = std::rc::Rc::new(key);
+                    storage_entries.push((key.clone(), Some(std::rc::Rc::new(entry))));
+                    footprint_entries.push((key, access));
+                    loaded_entries_count += 1;
+                }
+                None => {
+                    storage_logs.push(format!(
+                        "Ledger entry {:?} was provided but is not part of the transaction's footprint; ignoring",
+                        key
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(fp) = declared_footprint {
+        for key in fp.read_only.as_slice().iter().chain(fp.read_write.as_slice().iter()) {
+            if !seen_keys.contains(key) {
+                storage_logs.push(format!(
+                    "Footprint references {:?} but no matching ledger entry was provided",
+                    key
+                ));
+            }
+        }
+    }
+
+    // Initialize Host, wiring in the footprint-enforced storage built above
+    // so `execute_operations` runs against the real mainnet snapshot instead
+    // of an empty host.
+    let host = if declared_footprint.is_some() {
+        let footprint = soroban_env_host::storage::Footprint(footprint_entries.into_iter().collect());
+        let map: soroban_env_host::storage::StorageMap = storage_entries.into_iter().collect();
+        let storage = soroban_env_host::storage::Storage::with_enforcing_footprint_and_map(footprint, map);
+        soroban_env_host::Host::with_storage_and_budget(storage, soroban_env_host::budget::Budget::default())
+    } else {
+        storage_logs.push(
+            "Transaction has no Soroban footprint; decoded ledger entries were not injected into storage"
+                .to_string(),
+        );
+        soroban_env_host::Host::default()
+    };
     host.set_diagnostic_level(soroban_env_host::DiagnosticLevel::Debug)
         .unwrap();
 
@@ -165,38 +420,6 @@ fn main() {
         })
         .unwrap();
     }
-    // Populate Host Storage
-    let mut loaded_entries_count = 0;
-    if let Some(entries) = &request.ledger_entries {
-        for (key_xdr, entry_xdr) in entries {
-            // Decode Key
-            let _key = match base64::engine::general_purpose::STANDARD.decode(key_xdr) {
-                Ok(b) => match soroban_env_host::xdr::LedgerKey::from_xdr(
-                    &b,
-                    soroban_env_host::xdr::Limits::none(),
-                ) {
-                    Ok(k) => k,
-                    Err(e) => return send_error(format!("Failed to parse LedgerKey XDR: {}", e)),
-                },
-                Err(e) => return send_error(format!("Failed to decode LedgerKey Base64: {}", e)),
-            };
-
-            // Decode Entry
-            let _entry = match base64::engine::general_purpose::STANDARD.decode(entry_xdr) {
-                Ok(b) => match soroban_env_host::xdr::LedgerEntry::from_xdr(
-                    &b,
-                    soroban_env_host::xdr::Limits::none(),
-                ) {
-                    Ok(e) => e,
-                    Err(e) => return send_error(format!("Failed to parse LedgerEntry XDR: {}", e)),
-                },
-                Err(e) => return send_error(format!("Failed to decode LedgerEntry Base64: {}", e)),
-            };
-
-            // In real implementation, we'd inject into host storage here.
-            loaded_entries_count += 1;
-        }
-    }
 
     // Extract Operations from Envelope
     let operations = match &envelope {
@@ -206,120 +429,120 @@ fn main() {
             soroban_env_host::xdr::FeeBumpTransactionInnerTx::Tx(tx_v1) => &tx_v1.tx.operations,
         },
     };
+    let operations_count = operations.as_slice().len();
+    let exec_trace = execute_operations(&host, operations);
+
+    Ok(SimulationSuccess {
+        host,
+        exec_trace,
+        loaded_entries_count,
+        storage_logs,
+        operations_count,
+    })
+}
 
-    // Wrap the operation execution in panic protection
-    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-        execute_operations(&host, operations)
-    }));
+/// Logs plus the folded stack text collected while executing a
+/// transaction's operations, ready to feed to `inferno` or be returned
+/// as-is for external profiling tools.
+struct ExecutionTrace {
+    logs: Vec<String>,
+    cpu_folded: String,
+    mem_folded: String,
+}
 
-    // Budget and Reporting
-    let budget = host.budget_cloned();
-    let cpu_insns = budget.get_cpu_insns_consumed().unwrap_or(0);
-    let mem_bytes = budget.get_mem_bytes_consumed().unwrap_or(0);
+/// Runs each operation against `host`, invoking Soroban host functions for
+/// real so the budget actually moves and the wired-in storage actually
+/// gets read. Non-Soroban operations (classic payments, etc.) have
+/// nothing for the host to invoke, so they're logged and left as
+/// zero-delta frames.
+fn execute_operations(
+    host: &soroban_env_host::Host,
+    operations: &soroban_env_host::xdr::VecM<soroban_env_host::xdr::Operation, 100>,
+) -> ExecutionTrace {
+    let mut logs = vec![];
+    let mut cpu_folded = String::new();
+    let mut mem_folded = String::new();
 
-    let budget_usage = BudgetUsage {
-        cpu_instructions: cpu_insns,
-        memory_bytes: mem_bytes,
-        operations_count: operations.as_slice().len(),
-    };
+    for (i, op) in operations.as_slice().iter().enumerate() {
+        logs.push(format!("Processing operation {}: {:?}", i, op.body));
 
-    let optimization_report = if request.enable_optimization_advisor {
-        let advisor = GasOptimizationAdvisor::new();
-        let metrics = BudgetMetrics {
-            cpu_instructions: budget_usage.cpu_instructions,
-            memory_bytes: budget_usage.memory_bytes,
-            total_operations: budget_usage.operations_count,
-        };
-        Some(advisor.analyze(&metrics))
-    } else {
-        None
-    };
+        let budget_before = host.budget_cloned();
+        let cpu_before = budget_before.get_cpu_insns_consumed().unwrap_or(0);
+        let mem_before = budget_before.get_mem_bytes_consumed().unwrap_or(0);
 
-    let mut flamegraph_svg = None;
-    if request.profile.unwrap_or(false) {
-        // Simple simulated flamegraph for demonstration
-        let folded_data = format!("Total;CPU {}\nTotal;Memory {}\n", cpu_insns, mem_bytes);
-        let mut result = Vec::new();
-        let mut options = inferno::flamegraph::Options::default();
-        options.title = "Soroban Resource Consumption".to_string();
-        
-        if let Err(e) = inferno::flamegraph::from_reader(&mut options, folded_data.as_bytes(), &mut result) {
-            eprintln!("Failed to generate flamegraph: {}", e);
+        if let soroban_env_host::xdr::OperationBody::InvokeHostFunction(invoke_op) = &op.body {
+            match host.invoke_function(invoke_op.host_function.clone()) {
+                Ok(result) => logs.push(format!("Operation {} invocation result: {:?}", i, result)),
+                Err(e) => logs.push(format!("Operation {} invocation failed: {:?}", i, e)),
+            }
         } else {
-            flamegraph_svg = Some(String::from_utf8_lossy(&result).to_string());
+            logs.push(format!(
+                "Operation {} is not a Soroban host invocation; budget untouched",
+                i
+            ));
         }
-    }
 
-    match result {
-        Ok(exec_logs) => {
-            let events = match host.get_events() {
-                Ok(evs) => evs.0.iter().map(|e| format!("{:?}", e)).collect(),
-                Err(_) => vec!["Failed to retrieve events".to_string()],
-            };
+        let budget_after = host.budget_cloned();
+        let cpu_after = budget_after.get_cpu_insns_consumed().unwrap_or(0);
+        let mem_after = budget_after.get_mem_bytes_consumed().unwrap_or(0);
 
-            let mut final_logs = vec![
-                format!("Host Initialized with Budget: {:?}", budget),
-                format!("Loaded {} Ledger Entries", loaded_entries_count),
-            ];
-            final_logs.extend(exec_logs);
-
-            let response = SimulationResponse {
-                status: "success".to_string(),
-                error: None,
-                events,
-                logs: final_logs,
-                flamegraph: flamegraph_svg,
-                optimization_report,
-                budget_usage: Some(budget_usage),
-            };
-            println!("{}", serde_json::to_string(&response).unwrap());
-        }
-        Err(panic_info) => {
-            let panic_msg = if let Some(s) = panic_info.downcast_ref::<&str>() {
-                s.to_string()
-            } else if let Some(s) = panic_info.downcast_ref::<String>() {
-                s.clone()
-            } else {
-                "Unknown panic".to_string()
-            };
-
-            let response = SimulationResponse {
-                status: "error".to_string(),
-                error: Some(format!("Simulator panicked: {}", panic_msg)),
-                events: vec![],
-                logs: vec![format!("PANIC: {}", panic_msg)],
-                flamegraph: None,
-                optimization_report: None,
-                budget_usage: None,
-            };
-            println!("{}", serde_json::to_string(&response).unwrap());
-        }
+        let frame = format!("root;op{}_{}", i, operation_variant_name(&op.body));
+        cpu_folded.push_str(&format!("{} {}\n", frame, cpu_after.saturating_sub(cpu_before)));
+        mem_folded.push_str(&format!("{} {}\n", frame, mem_after.saturating_sub(mem_before)));
     }
+
+    ExecutionTrace { logs, cpu_folded, mem_folded }
 }
 
-fn execute_operations(
-    _host: &soroban_env_host::Host,
-    operations: &soroban_env_host::xdr::VecM<soroban_env_host::xdr::Operation, 100>,
-) -> Vec<String> {
-    let mut logs = vec![];
-    for (i, op) in operations.as_slice().iter().enumerate() {
-        logs.push(format!("Processing operation {}: {:?}", i, op.body));
-        // Placeholder for real host invocation
+/// Extracts the `OperationBody` variant name (e.g. `InvokeHostFunction`)
+/// from its `Debug` output, since the XDR enum doesn't expose it directly.
+fn operation_variant_name(body: &soroban_env_host::xdr::OperationBody) -> String {
+    let debug = format!("{:?}", body);
+    debug
+        .split(['(', ' '])
+        .next()
+        .unwrap_or("Unknown")
+        .to_string()
+}
+
+/// Renders a folded-stack flamegraph SVG, falling back to a single `root`
+/// frame when every delta collapsed to zero, since inferno rejects input
+/// whose counts sum to zero. The fallback frame's count is `total`, or 1
+/// when nothing was consumed at all.
+fn render_flamegraph(title: &str, folded: &str, total: u64) -> Option<String> {
+    let all_zero = folded
+        .lines()
+        .all(|line| line.rsplit(' ').next().map_or(true, |count| count.trim() == "0"));
+    let folded_data = if total == 0 || all_zero {
+        format!("root {}\n", total.max(1))
+    } else {
+        folded.to_string()
+    };
+
+    let mut options = inferno::flamegraph::Options::default();
+    options.title = title.to_string();
+
+    let mut svg = Vec::new();
+    match inferno::flamegraph::from_reader(&mut options, folded_data.as_bytes(), &mut svg) {
+        Ok(()) => Some(String::from_utf8_lossy(&svg).to_string()),
+        Err(e) => {
+            eprintln!("Failed to generate flamegraph: {}", e);
+            None
+        }
     }
-    logs
 }
 
-fn send_error(msg: String) {
-    let res = SimulationResponse {
+fn error_response(msg: String) -> SimulationResponse {
+    SimulationResponse {
         status: "error".to_string(),
         error: Some(msg),
         events: vec![],
         logs: vec![],
         flamegraph: None,
+        profile: None,
         optimization_report: None,
         budget_usage: None,
-    };
-    println!("{}", serde_json::to_string(&res).unwrap());
+    }
 }
 
 #[cfg(test)]
@@ -333,9 +556,75 @@ mod tests {
         assert_eq!(req.timestamp, Some(1738077842));
         assert_eq!(req.ledger_sequence, Some(1234));
     }
+
+    fn sample_account_key(byte: u8) -> soroban_env_host::xdr::LedgerKey {
+        use soroban_env_host::xdr::{AccountId, LedgerKey, LedgerKeyAccount, PublicKey, Uint256};
+        LedgerKey::Account(LedgerKeyAccount {
+            account_id: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([byte; 32]))),
+        })
+    }
+
+    #[test]
+    fn footprint_access_classifies_read_write_read_only_and_missing_keys() {
+        use soroban_env_host::xdr::LedgerFootprint;
+
+        let read_write_key = sample_account_key(1);
+        let read_only_key = sample_account_key(2);
+        let missing_key = sample_account_key(3);
+
+        let footprint = LedgerFootprint {
+            read_only: vec![read_only_key.clone()].try_into().unwrap(),
+            read_write: vec![read_write_key.clone()].try_into().unwrap(),
+        };
+
+        assert_eq!(
+            footprint_access(&footprint, &read_write_key),
+            Some(soroban_env_host::storage::AccessType::ReadWrite)
+        );
+        assert_eq!(
+            footprint_access(&footprint, &read_only_key),
+            Some(soroban_env_host::storage::AccessType::ReadOnly)
+        );
+        assert_eq!(footprint_access(&footprint, &missing_key), None);
+    }
+
+    #[test]
+    fn operation_variant_name_extracts_a_unit_variant() {
+        let body = soroban_env_host::xdr::OperationBody::EndSponsoringFutureReserves;
+        assert_eq!(operation_variant_name(&body), "EndSponsoringFutureReserves");
+    }
+
+    #[test]
+    fn execute_operations_logs_non_invocation_ops_with_zero_delta() {
+        let host = soroban_env_host::Host::default();
+        let op = soroban_env_host::xdr::Operation {
+            source_account: None,
+            body: soroban_env_host::xdr::OperationBody::EndSponsoringFutureReserves,
+        };
+        let operations: soroban_env_host::xdr::VecM<soroban_env_host::xdr::Operation, 100> =
+            vec![op].try_into().unwrap();
+
+        let trace = execute_operations(&host, &operations);
+
+        assert!(trace.logs.iter().any(|l| l.contains("not a Soroban host invocation")));
+        assert!(trace.cpu_folded.contains("op0_EndSponsoringFutureReserves 0"));
+    }
+
+    #[test]
+    fn render_flamegraph_falls_back_to_a_root_frame_when_folded_is_empty() {
+        let svg = render_flamegraph("empty", "", 0).expect("the zero-fallback must still render");
+        assert!(!svg.is_empty());
+    }
+
+    #[test]
+    fn render_flamegraph_renders_real_folded_data() {
+        let folded = "root;op0_InvokeHostFunction 42\n";
+        let svg = render_flamegraph("real", folded, 42).expect("non-empty folded data must render");
+        assert!(!svg.is_empty());
+    }
 }
 
-fn run_local_wasm_replay(wasm_path: &str, mock_args: &Option<Vec<String>>) {
+fn run_local_wasm_replay(wasm_path: &str, mock_args: &Option<Vec<String>>) -> SimulationResponse {
     use std::fs;
     use soroban_env_host::{
         xdr::{ScVal, ScSymbol, ScAddress},
@@ -354,7 +643,7 @@ fn run_local_wasm_replay(wasm_path: &str, mock_args: &Option<Vec<String>>) {
             bytes
         },
         Err(e) => {
-            return send_error(format!("Failed to read WASM file: {}", e));
+            return error_response(format!("Failed to read WASM file: {}", e));
         }
     };
 
@@ -389,15 +678,14 @@ fn run_local_wasm_replay(wasm_path: &str, mock_args: &Option<Vec<String>>) {
         "Execution: Skipped (Build Issue)".to_string(),
     ];
 
-    let response = SimulationResponse {
+    SimulationResponse {
         status: "success".to_string(),
         error: None,
         events,
         logs,
         flamegraph: None,
+        profile: None,
         optimization_report: None,
         budget_usage: None,
-    };
-
-    println!("{}", serde_json::to_string(&response).unwrap());
+    }
 }